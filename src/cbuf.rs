@@ -0,0 +1,64 @@
+use std::rc::Rc;
+
+/// Raw byte cursor over a source file's bytes, tracking the line/column
+/// position used by `SourceLoc` and an absolute byte `offset` that
+/// `Tokenizer` turns into `Span`s. Lexing stays byte-oriented here (no UTF-8
+/// validation on every peek); multi-byte sequences are decoded explicitly
+/// wherever the tokenizer actually needs them.
+pub struct CBuf {
+    bytes: Rc<[u8]>,
+    pub offset: usize,
+    pub line: i32,
+    pub column: i32,
+}
+
+impl CBuf {
+    pub fn create(content: &str) -> CBuf {
+        CBuf {
+            bytes: Rc::from(content.as_bytes()),
+            offset: 0,
+            line: 1,
+            column: 0,
+        }
+    }
+
+    pub fn is_eof(&self) -> bool {
+        self.offset >= self.bytes.len()
+    }
+
+    /// The byte at the current position, or `'\0'` past the end of input.
+    pub fn peek_1(&self) -> u8 {
+        self.peek_nth(0)
+    }
+
+    /// The 4 bytes starting at the current position (`'\0'`-padded past the
+    /// end of input), for the punctuator/operator lookahead in `next()`.
+    pub fn peek_4(&self) -> [u8; 4] {
+        [self.peek_nth(0), self.peek_nth(1), self.peek_nth(2), self.peek_nth(3)]
+    }
+
+    fn peek_nth(&self, n: usize) -> u8 {
+        *self.bytes.get(self.offset + n).unwrap_or(&b'\0')
+    }
+
+    /// Consumes and returns the byte at the current position, advancing
+    /// `offset` and the line/column position. Once EOF is reached this just
+    /// keeps handing back `'\0'` without moving `offset` any further.
+    pub fn next(&mut self) -> u8 {
+        if self.is_eof() {
+            return b'\0';
+        }
+
+        let b = self.bytes[self.offset];
+        self.offset += 1;
+
+        if b == b'\n' {
+            self.line += 1;
+            self.column = 0;
+        } else {
+            self.column += 1;
+        }
+
+        b
+    }
+}
@@ -0,0 +1,93 @@
+use crate::sloc::SourceLoc;
+
+/// Severity of a single diagnostic message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    Error,
+    Warning,
+}
+
+/// A single error/warning produced while scanning or parsing a file.
+///
+/// Unlike a `panic!`, a `Diagnostic` is just data: it carries the `loc` of the
+/// offending bytes and a human-readable `message`, and it is meant to be
+/// collected rather than to unwind the stack.
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub level: Level,
+    pub message: String,
+    pub loc: SourceLoc,
+}
+
+impl Diagnostic {
+    pub fn error(message: impl Into<String>, loc: SourceLoc) -> Self {
+        Diagnostic { level: Level::Error, message: message.into(), loc }
+    }
+
+    pub fn warning(message: impl Into<String>, loc: SourceLoc) -> Self {
+        Diagnostic { level: Level::Warning, message: message.into(), loc }
+    }
+
+    /// Renders this diagnostic the way a compiler front-end usually does:
+    /// `file:line:column: error: message`.
+    pub fn render(&self) -> String {
+        let kind = match self.level {
+            Level::Error => "error",
+            Level::Warning => "warning",
+        };
+        format!("{}:{}:{}: {}: {}", self.loc.file, self.loc.line, self.loc.column, kind, self.message)
+    }
+}
+
+/// Collects the `Diagnostic`s produced while tokenizing/parsing a single file.
+///
+/// The tokenizer owns one of these instead of calling `panic!`: every place
+/// that used to abort the process now pushes a `Diagnostic` here and returns
+/// a recovery token (usually `TOKEN_ERROR`) so scanning can continue.
+#[derive(Debug, Default)]
+pub struct DiagnosticBag {
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl DiagnosticBag {
+    pub fn new() -> Self {
+        DiagnosticBag { diagnostics: Vec::new() }
+    }
+
+    pub fn emit(&mut self, diag: Diagnostic) {
+        self.diagnostics.push(diag);
+    }
+
+    pub fn error(&mut self, message: impl Into<String>, loc: SourceLoc) {
+        self.emit(Diagnostic::error(message, loc));
+    }
+
+    pub fn warning(&mut self, message: impl Into<String>, loc: SourceLoc) {
+        self.emit(Diagnostic::warning(message, loc));
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.diagnostics.iter().any(|d| d.level == Level::Error)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.diagnostics.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.diagnostics.iter()
+    }
+
+    pub fn into_vec(self) -> Vec<Diagnostic> {
+        self.diagnostics
+    }
+
+    /// Renders every collected diagnostic, one per line, `file:line:col: level: message`.
+    pub fn render_all(&self) -> String {
+        self.diagnostics
+            .iter()
+            .map(Diagnostic::render)
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
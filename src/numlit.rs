@@ -0,0 +1,34 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Base {
+    Decimal,
+    Hex,
+    Octal,
+    Binary,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NumberKind {
+    Integer,
+    Float,
+}
+
+/// Structured result of scanning a numeric (pp-number) literal: which base
+/// it's written in, whether it's an integer or a float, its trailing suffix
+/// (`u32`, `L`, `f64`, ...), and whether scanning hit a malformed literal.
+#[derive(Debug, Clone)]
+pub struct NumberLiteral {
+    pub base: Base,
+    pub kind: NumberKind,
+    pub suffix: String,
+    pub had_error: bool,
+}
+
+/// Is `c` a digit in `base` (underscore separators are handled by the caller).
+pub fn is_digit_for_base(c: u8, base: Base) -> bool {
+    match base {
+        Base::Decimal => c.is_ascii_digit(),
+        Base::Hex => c.is_ascii_hexdigit(),
+        Base::Octal => (b'0'..=b'7').contains(&c),
+        Base::Binary => c == b'0' || c == b'1',
+    }
+}
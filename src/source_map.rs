@@ -0,0 +1,76 @@
+use std::rc::Rc;
+
+/// Stable id of a file registered with a `SourceMap`, modeled on rustc's
+/// `StableSourceFileId`. Stays valid across however many `Tokenizer`s are
+/// created for that file, so spans from different files can be compared and
+/// rendered through the one `SourceMap` that owns them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FileId(u32);
+
+/// A `(start, end)` byte range into the file named by `file`; `start` and
+/// `end` count from the beginning of that file, and `file` is what tells
+/// spans of different files in the one `SourceMap` apart. This
+/// replaces reconstructing a column from `buffer.column` and the token's
+/// length after the fact, which falls apart for multi-line tokens such as
+/// block comments or strings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub file: FileId,
+    pub start: u32,
+    pub end: u32,
+}
+
+struct SourceFile {
+    name: Rc<String>,
+    text: Rc<String>,
+    /// Byte offset of the start of each line, `line_starts[0] == 0`.
+    line_starts: Vec<u32>,
+}
+
+fn compute_line_starts(text: &str) -> Vec<u32> {
+    let mut line_starts = vec![0u32];
+    for (i, b) in text.bytes().enumerate() {
+        if b == b'\n' {
+            line_starts.push((i + 1) as u32);
+        }
+    }
+    line_starts
+}
+
+/// Owns the full text of every file the tokenizer(s) have scanned, and turns
+/// a `Span` back into `(file name, line, column)` for rendering.
+pub struct SourceMap {
+    files: Vec<SourceFile>,
+}
+
+impl SourceMap {
+    pub fn new() -> Self {
+        SourceMap { files: Vec::new() }
+    }
+
+    /// Registers a new file and returns its stable `FileId`.
+    pub fn register_file(&mut self, name: Rc<String>, text: Rc<String>) -> FileId {
+        let line_starts = compute_line_starts(&text);
+        let id = FileId(self.files.len() as u32);
+        self.files.push(SourceFile { name, text, line_starts });
+        id
+    }
+
+    pub fn file_name(&self, file: FileId) -> &Rc<String> {
+        &self.files[file.0 as usize].name
+    }
+
+    /// Resolves the start of `span` to `(file name, 1-based line, 1-based column)`.
+    pub fn lookup(&self, span: Span) -> (Rc<String>, u32, u32) {
+        let file = &self.files[span.file.0 as usize];
+
+        let line_index = match file.line_starts.binary_search(&span.start) {
+            Ok(i) => i,
+            Err(i) => i - 1,
+        };
+        let line_start = file.line_starts[line_index];
+        let column = span.start - line_start;
+
+        (Rc::clone(&file.name), (line_index + 1) as u32, column + 1)
+    }
+}
@@ -0,0 +1,48 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// A cheap, `Copy`able handle to an interned string, modeled on rustc's
+/// `syntax::symbol::Symbol`. Two identifiers that spell the same name always
+/// intern to the same `Symbol`, so comparing identifiers is a `u32` compare
+/// instead of a string compare.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Symbol(u32);
+
+impl Symbol {
+    pub fn as_u32(self) -> u32 {
+        self.0
+    }
+}
+
+/// Owns the interned strings and hands out `Symbol`s for them.
+///
+/// Interning is a two-step lookup avoidance: `intern` checks the map once to
+/// see if the string already has a `Symbol`, and `resolve` turns a `Symbol`
+/// back into a `&str` by indexing the backing `Vec` - no second hash lookup
+/// by string is ever needed once a `Symbol` has been handed out.
+pub struct Interner {
+    strings: Vec<Rc<str>>,
+    names: HashMap<Rc<str>, Symbol>,
+}
+
+impl Interner {
+    pub fn new() -> Self {
+        Interner { strings: Vec::new(), names: HashMap::new() }
+    }
+
+    pub fn intern(&mut self, s: &str) -> Symbol {
+        if let Some(&sym) = self.names.get(s) {
+            return sym;
+        }
+
+        let rc: Rc<str> = Rc::from(s);
+        let sym = Symbol(self.strings.len() as u32);
+        self.strings.push(Rc::clone(&rc));
+        self.names.insert(rc, sym);
+        sym
+    }
+
+    pub fn resolve(&self, sym: Symbol) -> &str {
+        &self.strings[sym.0 as usize]
+    }
+}
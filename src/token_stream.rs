@@ -0,0 +1,115 @@
+use std::collections::VecDeque;
+
+use crate::tok_flags::{IS_AT_BOL, LF_AFTER, WS_BEFORE};
+use crate::token::Token;
+use crate::tokenizer::Tokenizer;
+use crate::toktype::T;
+
+/// A caching, peekable view over a `Tokenizer`.
+///
+/// `Tokenizer::next()`/`tokenize()` only ever move forward, so a parser that
+/// needs to look ahead has nowhere to put the tokens it peeked at. This
+/// wraps a `Tokenizer` and buffers already-produced significant tokens in a
+/// `VecDeque`, doing the same `TOKEN_WS`/`TOKEN_LF` flag bookkeeping
+/// `Tokenizer::tokenize()` does inline, but lazily and one token at a time,
+/// so `peek`/`peek_nth` never re-scan bytes already consumed.
+pub struct TokenStream {
+    tokenizer: Tokenizer,
+    cache: VecDeque<Token>,
+    pending_line: Vec<Token>,
+    next_ws: bool,
+    eof_seen: bool,
+}
+
+impl TokenStream {
+    pub fn new(tokenizer: Tokenizer) -> Self {
+        TokenStream {
+            tokenizer,
+            cache: VecDeque::new(),
+            pending_line: Vec::new(),
+            next_ws: false,
+            eof_seen: false,
+        }
+    }
+
+    /// The next significant token, without consuming it.
+    pub fn peek(&mut self) -> &Token {
+        self.peek_nth(0)
+    }
+
+    /// The `k`-th significant token ahead (`peek_nth(0) == peek()`), without
+    /// consuming any of them. Once `TOKEN_EOF` has been reached, every
+    /// further index just keeps returning it, same as rustc's `peek_token`.
+    pub fn peek_nth(&mut self, k: usize) -> &Token {
+        self.fill_to(k);
+        let idx = k.min(self.cache.len() - 1);
+        self.cache.get(idx).expect("cache must hold at least the EOF token")
+    }
+
+    /// Consumes and returns the next significant token. Once `TOKEN_EOF` has
+    /// been reached it is never actually removed from the cache, so repeated
+    /// calls keep handing back EOF instead of panicking.
+    pub fn bump(&mut self) -> Token {
+        self.fill_to(0);
+        if self.eof_seen && self.cache.len() <= 1 {
+            return Token::make_eof();
+        }
+        self.cache.pop_front().expect("cache must hold at least the EOF token")
+    }
+
+    fn fill_to(&mut self, k: usize) {
+        while self.cache.len() <= k && !self.eof_seen {
+            self.pull_one();
+        }
+    }
+
+    /// Pulls raw tokens from the tokenizer until at least one new token lands
+    /// in `cache` (or EOF is reached). A single significant token may need
+    /// several raw tokens (the whitespace/line-feed ones carry no payload of
+    /// their own, they only set flags on their neighbours).
+    fn pull_one(&mut self) {
+        loop {
+            let mut t = self.tokenizer.next();
+
+            if t.is(T::TOKEN_EOF) {
+                for tok in self.pending_line.drain(..) {
+                    self.cache.push_back(tok);
+                }
+                self.cache.push_back(t);
+                self.eof_seen = true;
+                return;
+            }
+
+            if self.next_ws {
+                t.pos |= WS_BEFORE;
+                self.next_ws = false;
+            }
+
+            if t.is(T::TOKEN_LF) || t.is(T::TOKEN_COMMENT) {
+                if t.is(T::TOKEN_COMMENT) {
+                    self.pending_line.push(t);
+                }
+                if self.pending_line.is_empty() {
+                    continue;
+                }
+
+                let len = self.pending_line.len();
+                self.pending_line[len - 1].pos |= LF_AFTER;
+                self.pending_line[0].pos |= IS_AT_BOL;
+                self.pending_line[0].pos |= WS_BEFORE;
+
+                for tok in self.pending_line.drain(..) {
+                    self.cache.push_back(tok);
+                }
+                return;
+            }
+
+            if t.is(T::TOKEN_WS) {
+                self.next_ws = true;
+                continue;
+            }
+
+            self.pending_line.push(t);
+        }
+    }
+}
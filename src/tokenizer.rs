@@ -7,55 +7,119 @@ use std::rc::Rc;
 
 use crate::{ascii_util, tok_maps};
 use crate::cbuf::CBuf;
-use crate::ident::Ident;
-use crate::shared::shared_ptr;
+use crate::diagnostic::DiagnosticBag;
+use crate::numlit;
 use crate::sloc::SourceLoc;
+use crate::source_map::{FileId, SourceMap, Span};
+use crate::symbol::{Interner, Symbol};
 use crate::tok_flags::{IS_AT_BOL, LF_AFTER, USER_DEFINED_ID_BEGIN_UID, WS_BEFORE};
 use crate::tok_maps::Keywords;
 use crate::token::Token;
 use crate::toktype::T;
+use crate::unescape;
+use crate::unicode_util;
 
 pub struct Tokenizer {
     file_name: Rc<String>,
     buffer: CBuf,
     punct_map: HashMap<&'static str, T>,
-    idmap: HashMap<String, shared_ptr<Ident>>,
+    interner: Rc<RefCell<Interner>>,
+    keyword_map: HashMap<Symbol, T>,
+    source_map: Rc<RefCell<SourceMap>>,
+    file_id: FileId,
+    diagnostics: DiagnosticBag,
 }
 
 impl Tokenizer {
-    pub fn new_from_file(file_name: String, idmap: HashMap<String, shared_ptr<Ident>>) -> Self {
+    /// Breaking change from the old `new_from_file(file_name, idmap)`: the
+    /// caller now owns the `Interner` and `SourceMap` and hands in `Rc`s to
+    /// them, constructed once and shared across every `Tokenizer` for a
+    /// compilation (e.g. one per included file) so identifiers intern to the
+    /// same `Symbol`s and spans resolve through one shared coordinate space.
+    /// Driver code must be updated to build both up front:
+    /// `let interner = Rc::new(RefCell::new(Interner::new()));`
+    /// `let source_map = Rc::new(RefCell::new(SourceMap::new()));`
+    /// and pass `Rc::clone(&interner)` / `Rc::clone(&source_map)` into each
+    /// `new_from_file`/`new_from_string` call instead of a fresh `idmap`.
+    pub fn new_from_file(file_name: String, interner: Rc<RefCell<Interner>>, source_map: Rc<RefCell<SourceMap>>) -> Self {
         let content = read_file(&file_name);
         let mut punct_map = tok_maps::make_maps();
+        let keyword_map = make_keyword_map(&interner);
+        let file_name = Rc::new(file_name);
+        let file_id = source_map.borrow_mut().register_file(Rc::clone(&file_name), Rc::new(content.clone()));
 
         Tokenizer {
-            file_name: Rc::new(file_name),
+            file_name,
             buffer: CBuf::create(&content),
             punct_map,
-            idmap,
+            interner,
+            keyword_map,
+            source_map,
+            file_id,
+            diagnostics: DiagnosticBag::new(),
         }
     }
 
-    pub fn new_from_string(content: String, idmap: HashMap<String, shared_ptr<Ident>>) -> Self {
-        let maps = tok_maps::make_maps();
+    pub fn new_from_string(content: String, interner: Rc<RefCell<Interner>>, source_map: Rc<RefCell<SourceMap>>) -> Self {
         let mut punct_map = tok_maps::make_maps();
+        let keyword_map = make_keyword_map(&interner);
+        let file_name = Rc::new("<string-input>".to_string());
+        let file_id = source_map.borrow_mut().register_file(Rc::clone(&file_name), Rc::new(content.clone()));
 
         Tokenizer {
-            file_name: Rc::new("<string-input>".to_string()),
+            file_name,
             buffer: CBuf::create(&content),
             punct_map,
-            idmap,
+            interner,
+            keyword_map,
+            source_map,
+            file_id,
+            diagnostics: DiagnosticBag::new(),
         }
     }
 
+    /// Diagnostics collected so far by `next()`/`tokenize()`.
+    pub fn diagnostics(&self) -> &DiagnosticBag {
+        &self.diagnostics
+    }
+
+    /// `file:line:column: level: message`, one per line, for every collected diagnostic.
+    pub fn render_diagnostics(&self) -> String {
+        self.diagnostics.render_all()
+    }
+
+    /// Resolves a `Token`'s byte `span` to `(file name, line, column)` through
+    /// the shared `SourceMap`, so multi-file input resolves into one
+    /// coordinate space instead of each tokenizer reconstructing its own.
+    pub fn lookup_span(&self, span: Span) -> (Rc<String>, u32, u32) {
+        self.source_map.borrow().lookup(span)
+    }
+
     fn create_token(&self, tp: T, sb: &String) -> Token {
-        return Token::new(tp.clone()
+        let mut t = Token::new(tp.clone()
                           , sb.clone()
                           , self.build_sloc(&sb.clone()),
         );
+        t.span = Some(self.build_span(sb.len() as u32));
+        t
     }
 
-    fn create_token_spec_loc(&self, tp: T, sb: &String, loc: SourceLoc) -> Token {
-        return Token::new(tp.clone(), sb.clone(), loc);
+    fn create_token_spec_loc(&self, tp: T, sb: &String, loc: SourceLoc, start_offset: u32) -> Token {
+        let mut t = Token::new(tp.clone(), sb.clone(), loc);
+        t.span = Some(self.build_span_from(start_offset));
+        t
+    }
+
+    /// Byte span ending at the tokenizer's current position, `len` bytes wide.
+    fn build_span(&self, len: u32) -> Span {
+        let end = self.buffer.offset as u32;
+        self.build_span_from(end.saturating_sub(len))
+    }
+
+    /// Byte span from `start` (captured earlier, e.g. at an opening quote) to
+    /// the tokenizer's current position.
+    fn build_span_from(&self, start: u32) -> Span {
+        Span { file: self.file_id, start, end: self.buffer.offset as u32 }
     }
 
     pub fn next(&mut self) -> Token
@@ -74,7 +138,6 @@ impl Tokenizer {
             return Token::make_eof();
         }
 
-        // TODO: unicode whitespaces
         if c1 == b' ' || c1 == b'\t' {
             buffer.next();
             return Token::make_ws();
@@ -85,6 +148,17 @@ impl Tokenizer {
             return Token::make_lf();
         }
 
+        // non-ASCII whitespace (NEXT LINE, LTR/RTL marks, line/paragraph separators)
+        if c1 & 0x80 != 0 {
+            let (c, width) = unicode_util::decode_utf8_char(&begin);
+            if unicode_util::is_pattern_whitespace(c) {
+                for _ in 0..width {
+                    buffer.next();
+                }
+                return Token::make_ws();
+            }
+        }
+
         // comments // and /**/
         // TODO: doc.comments, begin location for error handling.
         if c1 == b'/' {
@@ -104,7 +178,9 @@ impl Tokenizer {
                     }
 
                     if tmp == b'\0' {
-                        panic!("no new-line at end of file..."); // TODO: location here
+                        let loc = self.build_sloc(&comments);
+                        self.diagnostics.error("no new-line at end of file", loc);
+                        return self.create_token(T::TOKEN_ERROR, &comments);
                     }
 
                     comments.push(tmp as char);
@@ -117,7 +193,9 @@ impl Tokenizer {
                 while !buffer.is_eof() {
                     let tmp = buffer.next();
                     if tmp == b'\0' {
-                        panic!("unclosed comment"); // TODO: location here
+                        let loc = self.build_sloc(&String::new());
+                        self.diagnostics.error("unclosed comment", loc);
+                        return self.create_token(T::TOKEN_ERROR, &String::new());
                     }
                     if tmp == b'/' && prev == b'*' {
                         return Token::make_ws();
@@ -129,11 +207,32 @@ impl Tokenizer {
 
         // identifiers
 
-        if ascii_util::is_letter(c1) {
+        let unicode_id_start = if c1 & 0x80 != 0 {
+            let (c, _) = unicode_util::decode_utf8_char(&begin);
+            unicode_util::is_xid_start(c)
+        } else {
+            false
+        };
+
+        if ascii_util::is_letter(c1) || unicode_id_start {
             let mut sb = String::new();
 
             while !buffer.is_eof() {
-                let peek1 = buffer.peek_1();
+                let peek4 = buffer.peek_4();
+                let peek1 = peek4[0];
+
+                if peek1 & 0x80 != 0 {
+                    let (c, width) = unicode_util::decode_utf8_char(&peek4);
+                    if !unicode_util::is_xid_continue(c) {
+                        break;
+                    }
+                    for _ in 0..width {
+                        buffer.next();
+                    }
+                    sb.push(c);
+                    continue;
+                }
+
                 let is_identifier_tail = ascii_util::is_letter(peek1) || ascii_util::is_dec(peek1);
                 if !is_identifier_tail {
                     break;
@@ -141,26 +240,19 @@ impl Tokenizer {
                 sb.push(buffer.next() as char);
             }
 
-            // Put the identifier we found in the hash.
+            // Intern the identifier we found.
             //
-            // All identifiers are shared between tokens.
-            // Each identifier is actually a unique pointer.
-            // For example: we have a loop in its simple form: for(int i=0; i<10; i+=1) {}
-            // The 'i' as an identifier will be presented in the hash once.
-            // The 'i' as a token will be presented three times, and each token will has a ref
-            // to the 'i' identifier, which is unique through the whole program, and contains a
-            // useful information about the 'named-identifier'. It may be a keyword, it may be a
-            // macro-name, it may be a special symbol, etc... So: we do not have to store somewhere
-            // a special hash-table for names if we can bind each name with a token in the
-            // token-tree. This simple trick works fine in C, with a raw-pointers, where we can
-            // compare these identifiers as pointers, and not as strings.
-            //
-            if !self.idmap.contains_key(&sb) {
-                let id = Ident::new(sb.clone());
-                self.idmap.insert(sb.clone(), shared_ptr::new(id));
-            }
-
-            return self.create_token(T::TOKEN_IDENT, &sb);
+            // All identifiers are shared between tokens: each spelling is
+            // interned exactly once, and every token carries the small
+            // `Copy` `Symbol` handle next to its own copy of the spelling. Comparing
+            // two identifiers, or checking whether one is a keyword, is then
+            // a `Symbol` (i.e. `u32`) compare instead of a string compare.
+            let sym = self.interner.borrow_mut().intern(&sb);
+
+            let tp = self.keyword_map.get(&sym).cloned().unwrap_or(T::TOKEN_IDENT);
+            let mut t = self.create_token(tp, &sb);
+            t.symbol = Some(sym);
+            return t;
         }
 
         // operators
@@ -219,41 +311,139 @@ impl Tokenizer {
                 return self.create_token(tp.clone(), &one);
             }
 
-            panic!("unknown operator {}", three); // TODO: location here
+            buffer.next();
+            let loc = self.build_sloc(&one);
+            self.diagnostics.error(format!("unknown operator `{}`", one), loc);
+            return self.create_token(T::TOKEN_ERROR, &one);
         }
 
         // numbers
-        // TODO: here we have to handle range patterns: 0..10, 0..=10, etc...
         if ascii_util::is_dec(c1) {
             let mut sb = String::new();
+            let mut had_error = false;
+
+            // base prefix: 0x / 0b / 0o
+            let base = if c1 == b'0' && (c2 == b'x' || c2 == b'X') {
+                sb.push(buffer.next() as char);
+                sb.push(buffer.next() as char);
+                numlit::Base::Hex
+            } else if c1 == b'0' && (c2 == b'b' || c2 == b'B') {
+                sb.push(buffer.next() as char);
+                sb.push(buffer.next() as char);
+                numlit::Base::Binary
+            } else if c1 == b'0' && (c2 == b'o' || c2 == b'O') {
+                sb.push(buffer.next() as char);
+                sb.push(buffer.next() as char);
+                numlit::Base::Octal
+            } else {
+                numlit::Base::Decimal
+            };
 
+            // integer part, '_' digit separators allowed
+            let mut has_digit = false;
             while !buffer.is_eof() {
-                let mut peekc = buffer.peek_1();
-                if ascii_util::is_dec(peekc) {
+                let peekc = buffer.peek_1();
+                if numlit::is_digit_for_base(peekc, base) {
+                    has_digit = true;
                     sb.push(buffer.next() as char);
                     continue;
-                } else if peekc == b'e' || peekc == b'E' || peekc == b'p' || peekc == b'P' {
+                }
+                if peekc == b'_' {
+                    sb.push(buffer.next() as char);
+                    continue;
+                }
+                break;
+            }
+            if !has_digit {
+                had_error = true;
+            }
+
+            // a decimal digit that can't belong to this base (e.g. `8`/`9`
+            // right after `0o1`, or `2`-`9` right after `0b1`) is a malformed
+            // literal, not the start of a suffix
+            while ascii_util::is_dec(buffer.peek_1()) && !numlit::is_digit_for_base(buffer.peek_1(), base) {
+                sb.push(buffer.next() as char);
+                had_error = true;
+            }
+
+            let mut kind = numlit::NumberKind::Integer;
+
+            // fractional part: a single '.' not followed by another '.'
+            // (so the `..` range operator in `0..10` isn't swallowed)
+            if base == numlit::Base::Decimal || base == numlit::Base::Hex {
+                let peek4 = buffer.peek_4();
+                if peek4[0] == b'.' && peek4[1] != b'.' {
                     sb.push(buffer.next() as char);
+                    kind = numlit::NumberKind::Float;
 
-                    peekc = buffer.peek_1();
-                    if peekc == b'-' || peekc == b'+' {
+                    // a trailing dot with no fractional digits (`1.`, `1.e5`) is a
+                    // legal C floating constant as long as there was an integer
+                    // part before it; don't flag it here, before the exponent
+                    // (if any) has even been scanned.
+                    while numlit::is_digit_for_base(buffer.peek_1(), base) || buffer.peek_1() == b'_' {
                         sb.push(buffer.next() as char);
                     }
-                    continue;
-                } else if peekc == b'.' || ascii_util::is_letter(peekc) {
+
+                    // a second '.' here (`1.2.3`) is a malformed double decimal
+                    // point, not the start of another fraction
+                    let peek4 = buffer.peek_4();
+                    if peek4[0] == b'.' && peek4[1] != b'.' {
+                        sb.push(buffer.next() as char);
+                        had_error = true;
+                        while numlit::is_digit_for_base(buffer.peek_1(), base) || buffer.peek_1() == b'_' {
+                            sb.push(buffer.next() as char);
+                        }
+                    }
+                }
+            }
+
+            // exponent: e/E for decimal, p/P for hex floats
+            let exponent_marker = buffer.peek_1();
+            let is_exponent_marker = (base == numlit::Base::Decimal && (exponent_marker == b'e' || exponent_marker == b'E'))
+                || (base == numlit::Base::Hex && (exponent_marker == b'p' || exponent_marker == b'P'));
+            if is_exponent_marker {
+                sb.push(buffer.next() as char);
+                kind = numlit::NumberKind::Float;
+
+                let sign = buffer.peek_1();
+                if sign == b'-' || sign == b'+' {
                     sb.push(buffer.next() as char);
-                    continue;
                 }
 
-                break;
+                if !ascii_util::is_dec(buffer.peek_1()) {
+                    had_error = true;
+                }
+                while ascii_util::is_dec(buffer.peek_1()) {
+                    sb.push(buffer.next() as char);
+                }
             }
 
-            return self.create_token(T::TOKEN_NUMBER, &sb);
+            // trailing suffix: u32, L, UL, f64, ... — must start with a
+            // letter, so a base-invalid digit is never mistaken for one
+            let mut suffix = String::new();
+            if ascii_util::is_letter(buffer.peek_1()) {
+                while ascii_util::is_letter(buffer.peek_1()) || ascii_util::is_dec(buffer.peek_1()) {
+                    let ch = buffer.next() as char;
+                    sb.push(ch);
+                    suffix.push(ch);
+                }
+            }
+
+            if had_error {
+                let loc = self.build_sloc(&sb);
+                self.diagnostics.error(format!("malformed numeric literal `{}`", sb), loc);
+            }
+
+            let mut t = self.create_token(T::TOKEN_NUMBER, &sb);
+            t.num = Some(numlit::NumberLiteral { base, kind, suffix, had_error });
+            return t;
         }
 
         // string, char
         // TODO: here we have to handle lifetime patterns: 'a, 'static, etc...
         if c1 == b'\"' || c1 == b'\'' {
+            let start_offset = buffer.offset as u32;
+            let mut closed = false;
             let end = buffer.next(); // skip the quote
 
             let line = buffer.line;
@@ -262,27 +452,44 @@ impl Tokenizer {
 
             let mut sb = String::new();
             while !buffer.is_eof() {
-                let next = buffer.next();
+                let la = buffer.peek_1();
 
-                if next == b'\0' {
-                    panic!("unclosed string"); // TODO: location here
-                }
-                if next == b'\n' {
-                    // panic!("end of line in string");
+                if la == b'\0' {
+                    self.diagnostics.error("unclosed string", loc.clone());
+                    return self.create_token_spec_loc(T::TOKEN_ERROR, &sb, loc, start_offset);
                 }
-                if next == end {
+                if la == end {
+                    buffer.next();
+                    closed = true;
                     break;
                 }
 
-                if next == b'\\' {
+                if la == b'\\' {
                     // escaped character
+                    buffer.next();
                     sb.push_str("\\");
                     sb.push(buffer.next() as char);
+                } else if la & 0x80 != 0 {
+                    // multi-byte UTF-8 content: decode it as one scalar value
+                    // instead of pushing each byte as its own Latin-1 `char`,
+                    // so `unescape_literal`'s UTF-8 re-decode below sees the
+                    // real codepoint rather than mojibake
+                    let (c, width) = unicode_util::decode_utf8_char(&buffer.peek_4());
+                    for _ in 0..width {
+                        buffer.next();
+                    }
+                    sb.push(c);
                 } else {
                     // normal symbol
-                    sb.push(next as char);
+                    buffer.next();
+                    sb.push(la as char);
                 }
             }
+            if !closed {
+                // input ended before the closing quote
+                self.diagnostics.error("unclosed string", loc.clone());
+                return self.create_token_spec_loc(T::TOKEN_ERROR, &sb, loc, start_offset);
+            }
 
             // string
 
@@ -290,11 +497,29 @@ impl Tokenizer {
             repr.push_str(&sb.clone());
             repr.push(end as char);
 
+            let unescaped = unescape::unescape_literal(&sb);
+            for (offset, err) in &unescaped.errors {
+                // +1 to skip past the opening quote
+                let err_loc = SourceLoc::new(Rc::clone(&self.file_name), loc.line, loc.column + *offset as i32 + 1);
+                self.diagnostics.error(err.message(), err_loc);
+            }
+            let decoded: String = unescaped.chars.iter().collect();
+
             if end == b'\"' {
-                return self.create_token_spec_loc(T::TOKEN_STRING, &repr, loc);
+                let mut t = self.create_token_spec_loc(T::TOKEN_STRING, &repr, loc, start_offset);
+                t.decoded = Some(decoded);
+                return t;
+            }
+
+            if unescaped.chars.is_empty() {
+                self.diagnostics.error("empty character literal", loc.clone());
+            } else if unescaped.chars.len() > 1 {
+                self.diagnostics.error("character literal may only contain one code point", loc.clone());
             }
 
-            return self.create_token_spec_loc(T::TOKEN_CHAR, &repr, loc);
+            let mut t = self.create_token_spec_loc(T::TOKEN_CHAR, &repr, loc, start_offset);
+            t.decoded = Some(decoded);
+            return t;
         }
 
         // other ASCII
@@ -305,9 +530,36 @@ impl Tokenizer {
             return self.create_token(tp.clone(), &one);
         }
 
+        // non-ASCII punctuation that's easily mistaken for an ASCII operator,
+        // e.g. a Greek question mark where a semicolon was meant
+        if c1 & 0x80 != 0 {
+            let (c, width) = unicode_util::decode_utf8_char(&begin);
+            if let Some(confusable) = unicode_util::lookup_confusable(c) {
+                for _ in 0..width {
+                    buffer.next();
+                }
+                let loc = self.build_sloc(&c.to_string());
+                self.diagnostics.error(
+                    format!(
+                        "unicode character '{}' ({}) looks like the ASCII character '{}', but isn't",
+                        confusable.unicode, confusable.name, confusable.ascii
+                    ),
+                    loc,
+                );
+                return self.create_token(T::TOKEN_ERROR, &c.to_string());
+            }
+        }
+
         // we do not really know what this char means
-        let unknown = String::from(c1 as char);
-        buffer.next(); // XXX
+        let (unknown_char, width) = if c1 & 0x80 != 0 {
+            unicode_util::decode_utf8_char(&begin)
+        } else {
+            (c1 as char, 1)
+        };
+        let unknown = unknown_char.to_string();
+        for _ in 0..width {
+            buffer.next(); // XXX
+        }
         return self.create_token(T::TOKEN_ERROR, &unknown);
     }
 
@@ -321,16 +573,6 @@ impl Tokenizer {
         while !self.buffer.is_eof() {
             let mut t = self.next();
 
-            if t.is(T::TOKEN_IDENT) {
-                let opt = self.idmap.get(&t.val);
-                if opt.is_none() {
-                    panic!("cannot find the name `{}` in the hash-table", &t.val);
-                }
-
-                let x = opt.unwrap();
-                t.id = Option::from(shared_ptr::_cloneref(x));
-            }
-
             if t.is(T::TOKEN_EOF) {
                 for tok in line {
                     tokenlist.push(tok);
@@ -378,7 +620,6 @@ impl Tokenizer {
             line.push(t);
         }
 
-        println!("map len: {}", self.idmap.len());
         return tokenlist;
     }
 
@@ -395,6 +636,19 @@ impl Tokenizer {
 }
 
 
+/// Precomputes the keyword spelling -> token-type table as `Symbol`s, so that
+/// classifying an identifier as a keyword during `next()` is a `Symbol` key
+/// lookup rather than a string compare against every keyword.
+fn make_keyword_map(interner: &Rc<RefCell<Interner>>) -> HashMap<Symbol, T> {
+    let mut keyword_map = HashMap::new();
+    let mut interner = interner.borrow_mut();
+    for (spelling, tp) in Keywords() {
+        let sym = interner.intern(spelling);
+        keyword_map.insert(sym, tp);
+    }
+    keyword_map
+}
+
 fn read_file(filename: &str) -> String {
     let mut input = String::new();
     let mut fp = io::stdin();
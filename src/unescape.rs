@@ -0,0 +1,142 @@
+//! Decodes the escape sequences inside a string/char literal, modeled on
+//! rustc's lexer `unescape` module: it walks the raw inner text of a literal
+//! (the bytes between the quotes, backslashes still literal) and produces
+//! the decoded `char` sequence, reporting every malformed escape at its
+//! offset inside the literal instead of silently passing it through.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EscapeError {
+    /// `\` followed by EOF or a character that isn't a recognized escape.
+    UnknownEscape,
+    /// `\x` not followed by exactly two hex digits.
+    InvalidHexEscape,
+    /// `\x` escape whose value is out of the `0x00..=0x7F` ASCII range.
+    HexEscapeOutOfRange,
+    /// `\u` not followed by `{`.
+    MissingUnicodeBrace,
+    /// `\u{...}` whose digits aren't valid hex.
+    InvalidUnicodeEscape,
+    /// `\u{...}` missing the closing `}`.
+    UnclosedUnicodeEscape,
+    /// `\u{...}` encodes a value that isn't a valid Unicode scalar value.
+    InvalidCodepoint,
+}
+
+impl EscapeError {
+    pub fn message(&self) -> &'static str {
+        match self {
+            EscapeError::UnknownEscape => "unknown character escape",
+            EscapeError::InvalidHexEscape => "numeric character escape is not exactly two hex digits",
+            EscapeError::HexEscapeOutOfRange => "this form of character escape may only be used with characters in the range [\\x00-\\x7f]",
+            EscapeError::MissingUnicodeBrace => "expected `{` after `\\u`",
+            EscapeError::InvalidUnicodeEscape => "invalid character in unicode escape",
+            EscapeError::UnclosedUnicodeEscape => "unterminated unicode escape",
+            EscapeError::InvalidCodepoint => "invalid unicode character escape",
+        }
+    }
+}
+
+/// The decoded scalars of a literal, plus every escape error paired with the
+/// byte offset inside `src` where its escape starts, suitable for anchoring
+/// a diagnostic inside the literal's span.
+pub struct Unescaped {
+    pub chars: Vec<char>,
+    pub errors: Vec<(usize, EscapeError)>,
+}
+
+/// Decodes every escape in `src`, the raw inner text of a string or char
+/// literal (quotes already stripped).
+pub fn unescape_literal(src: &str) -> Unescaped {
+    let bytes = src.as_bytes();
+    let mut chars = Vec::new();
+    let mut errors = Vec::new();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i] != b'\\' {
+            // re-decode as UTF-8 so multi-byte source characters survive
+            let rest = &src[i..];
+            let c = rest.chars().next().unwrap();
+            chars.push(c);
+            i += c.len_utf8();
+            continue;
+        }
+
+        let escape_start = i;
+        i += 1; // skip '\'
+
+        if i >= bytes.len() {
+            errors.push((escape_start, EscapeError::UnknownEscape));
+            break;
+        }
+
+        match bytes[i] {
+            b'n' => { chars.push('\n'); i += 1; }
+            b't' => { chars.push('\t'); i += 1; }
+            b'r' => { chars.push('\r'); i += 1; }
+            b'\\' => { chars.push('\\'); i += 1; }
+            b'\"' => { chars.push('\"'); i += 1; }
+            b'\'' => { chars.push('\''); i += 1; }
+            b'0' => { chars.push('\0'); i += 1; }
+            b'x' => {
+                i += 1;
+                let digits = bytes.get(i..i + 2)
+                    .filter(|d| d.iter().all(u8::is_ascii_hexdigit))
+                    .and_then(|d| std::str::from_utf8(d).ok());
+                match digits.and_then(|d| u8::from_str_radix(d, 16).ok()) {
+                    Some(value) if value <= 0x7F => {
+                        chars.push(value as char);
+                        i += 2;
+                    }
+                    Some(_) => {
+                        errors.push((escape_start, EscapeError::HexEscapeOutOfRange));
+                        i += 2;
+                    }
+                    None => {
+                        errors.push((escape_start, EscapeError::InvalidHexEscape));
+                    }
+                }
+            }
+            b'u' => {
+                i += 1;
+                if bytes.get(i) != Some(&b'{') {
+                    errors.push((escape_start, EscapeError::MissingUnicodeBrace));
+                    continue;
+                }
+                i += 1;
+
+                let digits_start = i;
+                while bytes.get(i).map_or(false, u8::is_ascii_hexdigit) {
+                    i += 1;
+                }
+                let digits = &src[digits_start..i];
+
+                if bytes.get(i) != Some(&b'}') {
+                    match src[i..].find('}') {
+                        Some(close) => {
+                            errors.push((escape_start, EscapeError::InvalidUnicodeEscape));
+                            i += close + 1;
+                        }
+                        None => errors.push((escape_start, EscapeError::UnclosedUnicodeEscape)),
+                    }
+                    continue;
+                }
+                i += 1; // skip '}'
+
+                match u32::from_str_radix(digits, 16) {
+                    Ok(value) => match char::from_u32(value) {
+                        Some(c) => chars.push(c),
+                        None => errors.push((escape_start, EscapeError::InvalidCodepoint)),
+                    },
+                    Err(_) => errors.push((escape_start, EscapeError::InvalidUnicodeEscape)),
+                }
+            }
+            _ => {
+                errors.push((escape_start, EscapeError::UnknownEscape));
+                i += src[i..].chars().next().map_or(1, char::len_utf8);
+            }
+        }
+    }
+
+    Unescaped { chars, errors }
+}
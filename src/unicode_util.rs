@@ -0,0 +1,94 @@
+//! Unicode helpers for the tokenizer, mirroring the subset of properties
+//! rustc's `StringReader` relies on: `XID_Start`/`XID_Continue` for
+//! identifiers, `Pattern_White_Space` for whitespace, and a small table of
+//! "confusable" characters that look like ASCII punctuation but aren't.
+
+/// Decodes one UTF-8 scalar value from the front of `bytes`.
+///
+/// `bytes` must contain at least the leading byte of the sequence (as given
+/// by `CBuf::peek_4`); returns the decoded `char` and the number of bytes it
+/// occupies. Falls back to the replacement character and a 1-byte width on
+/// malformed input so callers always make forward progress.
+pub fn decode_utf8_char(bytes: &[u8]) -> (char, usize) {
+    let b0 = bytes[0];
+
+    let width = if b0 & 0x80 == 0x00 {
+        1
+    } else if b0 & 0xE0 == 0xC0 {
+        2
+    } else if b0 & 0xF0 == 0xE0 {
+        3
+    } else if b0 & 0xF8 == 0xF0 {
+        4
+    } else {
+        return (char::REPLACEMENT_CHARACTER, 1);
+    };
+
+    if width > bytes.len() {
+        return (char::REPLACEMENT_CHARACTER, 1);
+    }
+
+    match std::str::from_utf8(&bytes[..width]) {
+        Ok(s) => match s.chars().next() {
+            Some(c) => (c, width),
+            None => (char::REPLACEMENT_CHARACTER, 1),
+        },
+        Err(_) => (char::REPLACEMENT_CHARACTER, 1),
+    }
+}
+
+/// `XID_Start`: valid first character of an identifier (ASCII letters, `_`,
+/// plus any non-ASCII alphabetic/XID-start codepoint).
+pub fn is_xid_start(c: char) -> bool {
+    c == '_' || unicode_xid::UnicodeXID::is_xid_start(c)
+}
+
+/// `XID_Continue`: valid non-first character of an identifier.
+pub fn is_xid_continue(c: char) -> bool {
+    unicode_xid::UnicodeXID::is_xid_continue(c)
+}
+
+/// `Pattern_White_Space`, the seven ranges rustc's lexer treats as
+/// whitespace. Deliberately narrower than `White_Space`: characters like
+/// NBSP (U+00A0) are `White_Space` but not `Pattern_White_Space`, so they
+/// fall through to the confusables check below instead of being silently
+/// swallowed as whitespace.
+pub fn is_pattern_whitespace(c: char) -> bool {
+    matches!(
+        c,
+        '\u{0009}'..='\u{000D}'
+            | '\u{0020}'
+            | '\u{0085}'
+            | '\u{200E}'
+            | '\u{200F}'
+            | '\u{2028}'
+            | '\u{2029}'
+    )
+}
+
+/// A confusable character at an operator/punct position together with the
+/// ASCII punctuation a reader almost certainly meant, e.g. the Greek question
+/// mark `;` (U+037E) for a semicolon. Modeled on rustc's `unicode_chars`
+/// table used in `emit_unicode_character_diagnostic`.
+pub struct Confusable {
+    pub unicode: char,
+    pub ascii: char,
+    pub name: &'static str,
+}
+
+const CONFUSABLES: &[Confusable] = &[
+    Confusable { unicode: '\u{00A0}', ascii: ' ', name: "NO-BREAK SPACE" },
+    Confusable { unicode: '\u{037E}', ascii: ';', name: "GREEK QUESTION MARK" },
+    Confusable { unicode: '\u{FF1B}', ascii: ';', name: "FULLWIDTH SEMICOLON" },
+    Confusable { unicode: '\u{FF0C}', ascii: ',', name: "FULLWIDTH COMMA" },
+    Confusable { unicode: '\u{FF08}', ascii: '(', name: "FULLWIDTH LEFT PARENTHESIS" },
+    Confusable { unicode: '\u{FF09}', ascii: ')', name: "FULLWIDTH RIGHT PARENTHESIS" },
+    Confusable { unicode: '\u{FF1A}', ascii: ':', name: "FULLWIDTH COLON" },
+    Confusable { unicode: '\u{2212}', ascii: '-', name: "MINUS SIGN" },
+    Confusable { unicode: '\u{00B7}', ascii: '.', name: "MIDDLE DOT" },
+];
+
+/// Looks up `c` in the confusables table.
+pub fn lookup_confusable(c: char) -> Option<&'static Confusable> {
+    CONFUSABLES.iter().find(|conf| conf.unicode == c)
+}